@@ -0,0 +1,268 @@
+use anyhow::{anyhow, Result};
+
+use crate::adoptjdk;
+
+/// Identifier persisted in the per-major `.jpre_dist` marker and accepted from
+/// config, so a cached JDK always remembers which vendor produced it.
+pub const TEMURIN: &str = "temurin";
+pub const ZULU: &str = "zulu";
+pub const CORRETTO: &str = "corretto";
+pub const GRAALVM: &str = "graalvm";
+
+/// A source of JDK binaries. Each implementation knows its own download URL
+/// template and the OS/arch matrix that vendor publishes under.
+pub trait Distribution {
+    /// Stable identifier stored in the `.jpre_dist` marker.
+    fn id(&self) -> &'static str;
+
+    /// Fetch the latest binary for the given major version.
+    fn latest_binary(&self, major: u8) -> Result<attohttpc::Response>;
+
+    /// URL of the SHA-256 checksum published alongside the latest binary.
+    fn checksum_url(&self, major: u8) -> Result<String>;
+
+    /// The archive extension this vendor ships for the host (`.tar.gz`, or
+    /// `.zip` on Windows). Used to choose an extractor when the download
+    /// response carries no `Content-Disposition` filename to parse, as the
+    /// non-Adoptium CDNs may not send one.
+    fn archive_extension(&self) -> &'static str {
+        if std::env::consts::OS == "windows" {
+            ".zip"
+        } else {
+            ".tar.gz"
+        }
+    }
+
+    /// The latest `JAVA_VERSION` string the vendor advertises for this major,
+    /// used for staleness checks. Returns `None` when the vendor exposes no
+    /// cheap way to learn it without downloading the binary.
+    fn latest_version(&self, _major: u8) -> Result<Option<String>> {
+        Ok(None)
+    }
+
+    /// Fetch and return the expected SHA-256 hex digest. Fails closed: a vendor
+    /// that advertises a [`checksum_url`](Distribution::checksum_url) but whose
+    /// fetch errors or returns non-2xx aborts the install rather than silently
+    /// skipping verification. Vendors with no checksum override this to return
+    /// `Ok(None)`.
+    fn latest_checksum(&self, major: u8) -> Result<Option<String>> {
+        let url = self.checksum_url(major)?;
+        let response = attohttpc::get(&url).send()?;
+        if !response.is_success() {
+            return Err(anyhow!(
+                "Failed to fetch checksum ({}): HTTP {}",
+                url,
+                response.status()
+            ));
+        }
+        // Checksum files are usually "<hex>  <filename>"; keep only the digest.
+        let text = response.text()?;
+        Ok(text.split_whitespace().next().map(|s| s.to_string()))
+    }
+}
+
+/// The host's OS token as the common vendor URL templates spell it.
+fn os_token() -> Result<&'static str> {
+    Ok(match std::env::consts::OS {
+        "linux" => "linux",
+        "macos" => "macos",
+        "windows" => "windows",
+        other => return Err(anyhow!("unsupported OS {}", other)),
+    })
+}
+
+/// The host's architecture token as the common vendor URL templates spell it.
+fn arch_token() -> Result<&'static str> {
+    Ok(match std::env::consts::ARCH {
+        "x86_64" => "x64",
+        "aarch64" => "aarch64",
+        other => return Err(anyhow!("unsupported architecture {}", other)),
+    })
+}
+
+/// Eclipse Temurin, served through the existing Adoptium API helper.
+pub struct Temurin;
+
+impl Distribution for Temurin {
+    fn id(&self) -> &'static str {
+        TEMURIN
+    }
+
+    fn latest_binary(&self, major: u8) -> Result<attohttpc::Response> {
+        adoptjdk::get_latest_jdk_binary(major)
+    }
+
+    fn checksum_url(&self, major: u8) -> Result<String> {
+        Ok(adoptjdk::latest_jdk_checksum_url(major))
+    }
+
+    fn latest_checksum(&self, major: u8) -> Result<Option<String>> {
+        adoptjdk::get_latest_jdk_checksum(major)
+    }
+
+    fn latest_version(&self, major: u8) -> Result<Option<String>> {
+        adoptjdk::get_latest_jdk_version(major).map(Some)
+    }
+}
+
+/// Azul Zulu community builds. Azul does not expose a stable "latest" download
+/// URL, so the concrete binary and its SHA-256 are resolved through the Azul
+/// metadata API, which returns both in one JSON response.
+pub struct Zulu;
+
+#[derive(serde::Deserialize)]
+struct ZuluPackage {
+    download_url: String,
+    #[serde(default)]
+    sha256_hash: Option<String>,
+}
+
+impl Zulu {
+    /// Azul's metadata spells architectures as a base plus a bit-width, rather
+    /// than the single `x64`/`aarch64` tokens other vendors use.
+    fn arch() -> Result<&'static str> {
+        Ok(match std::env::consts::ARCH {
+            "x86_64" => "x86",
+            "aarch64" => "arm",
+            other => return Err(anyhow!("unsupported architecture {}", other)),
+        })
+    }
+
+    fn metadata_url(major: u8) -> Result<String> {
+        Ok(format!(
+            "https://api.azul.com/metadata/v1/zulu/packages/?java_version={}&os={}&arch={}&hw_bitness=64&archive_type=tar.gz&java_package_type=jdk&javafx_bundled=false&latest=true&release_status=ga&page=1&page_size=1",
+            major,
+            os_token()?,
+            Zulu::arch()?,
+        ))
+    }
+
+    fn latest_package(major: u8) -> Result<ZuluPackage> {
+        let url = Zulu::metadata_url(major)?;
+        let response = attohttpc::get(&url).send()?;
+        if !response.is_success() {
+            return Err(anyhow!(
+                "Failed to query Azul metadata ({}): HTTP {}",
+                url,
+                response.status()
+            ));
+        }
+        let packages: Vec<ZuluPackage> = serde_json::from_str(&response.text()?)
+            .map_err(|err| anyhow!("Failed to parse Azul metadata: {}", err))?;
+        packages
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow!("No Zulu package available for Java {}", major))
+    }
+}
+
+impl Distribution for Zulu {
+    fn id(&self) -> &'static str {
+        ZULU
+    }
+
+    fn latest_binary(&self, major: u8) -> Result<attohttpc::Response> {
+        Ok(attohttpc::get(Zulu::latest_package(major)?.download_url).send()?)
+    }
+
+    fn checksum_url(&self, major: u8) -> Result<String> {
+        // The digest rides along in the metadata response; surface that endpoint.
+        Zulu::metadata_url(major)
+    }
+
+    fn latest_checksum(&self, major: u8) -> Result<Option<String>> {
+        Ok(Zulu::latest_package(major)?.sha256_hash)
+    }
+}
+
+/// Amazon Corretto.
+pub struct Corretto;
+
+impl Corretto {
+    fn binary_url(major: u8) -> Result<String> {
+        Ok(format!(
+            "https://corretto.aws/downloads/latest/amazon-corretto-{}-{}-{}-jdk.tar.gz",
+            major,
+            arch_token()?,
+            os_token()?,
+        ))
+    }
+}
+
+impl Distribution for Corretto {
+    fn id(&self) -> &'static str {
+        CORRETTO
+    }
+
+    fn latest_binary(&self, major: u8) -> Result<attohttpc::Response> {
+        Ok(attohttpc::get(Corretto::binary_url(major)?).send()?)
+    }
+
+    fn checksum_url(&self, major: u8) -> Result<String> {
+        // Corretto's `latest_checksum` endpoint serves an MD5 digest, which the
+        // SHA-256 comparison in `finish_extract` cannot use. See `latest_checksum`.
+        Ok(format!(
+            "https://corretto.aws/downloads/latest_checksum/amazon-corretto-{}-{}-{}-jdk.tar.gz",
+            major,
+            arch_token()?,
+            os_token()?,
+        ))
+    }
+
+    fn latest_checksum(&self, _major: u8) -> Result<Option<String>> {
+        // The only checksum Corretto publishes here is MD5, not SHA-256, so we
+        // advertise no SHA-256 rather than aborting every install on a mismatch.
+        Ok(None)
+    }
+}
+
+/// Oracle GraalVM, downloaded from `download.oracle.com` under the Oracle
+/// free-use license terms. (This is *not* the MIT-licensed GraalVM Community
+/// Edition, which is published separately at `github.com/graalvm`.)
+pub struct GraalVm;
+
+impl GraalVm {
+    fn binary_url(major: u8) -> Result<String> {
+        Ok(format!(
+            "https://download.oracle.com/graalvm/{}/latest/graalvm-jdk-{}_{}-{}_bin.tar.gz",
+            major,
+            major,
+            os_token()?,
+            arch_token()?,
+        ))
+    }
+}
+
+impl Distribution for GraalVm {
+    fn id(&self) -> &'static str {
+        GRAALVM
+    }
+
+    fn latest_binary(&self, major: u8) -> Result<attohttpc::Response> {
+        Ok(attohttpc::get(GraalVm::binary_url(major)?).send()?)
+    }
+
+    fn checksum_url(&self, major: u8) -> Result<String> {
+        Ok(format!("{}.sha256", GraalVm::binary_url(major)?))
+    }
+}
+
+/// Resolve a distribution by its stored identifier.
+pub fn for_id(id: &str) -> Option<Box<dyn Distribution>> {
+    match id {
+        TEMURIN => Some(Box::new(Temurin)),
+        ZULU => Some(Box::new(Zulu)),
+        CORRETTO => Some(Box::new(Corretto)),
+        GRAALVM => Some(Box::new(GraalVm)),
+        _ => None,
+    }
+}
+
+/// The distribution to use when a major has no `.jpre_dist` marker yet, taken
+/// from config and falling back to Temurin.
+pub fn configured_default() -> Box<dyn Distribution> {
+    crate::config::default_distribution()
+        .as_deref()
+        .and_then(for_id)
+        .unwrap_or_else(|| Box::new(Temurin))
+}
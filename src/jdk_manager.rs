@@ -2,16 +2,18 @@ use std::collections::HashMap;
 use std::ffi::CStr;
 use std::fs::{create_dir_all, File};
 use std::io;
-use std::io::{Read, BufReader};
+use std::io::{Read, BufReader, Seek, SeekFrom};
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 
 use anyhow::{anyhow, Context, Result};
+use sha2::{Digest, Sha256};
 use indicatif::{MultiProgress, ProgressDrawTarget};
 use log::debug;
 use once_cell::sync::Lazy;
 use tempdir::TempDir;
 
-use crate::adoptjdk;
+use crate::distribution::{self, Distribution};
 use crate::content_disposition_parser::parse_filename;
 use crate::http_failure::handle_response_fail;
 use crate::progress::new_progress_bar;
@@ -42,16 +44,53 @@ pub fn get_current_jdk() -> Result<String> {
 }
 
 const FINISHED_MARKER: &str = ".jdk_marker";
+const DIST_MARKER: &str = ".jpre_dist";
+
+/// Remove a path regardless of whether it is a symlink, a plain file, or a
+/// directory tree — `remove_dir_all` alone errors on the symlinked entries that
+/// registered system JDKs leave behind.
+fn remove_any(path: &Path) -> io::Result<()> {
+    std::fs::remove_file(path).or_else(|_| std::fs::remove_dir_all(path))
+}
+
+/// Whether `BASE_PATH/<major>` holds a usable JDK. A managed install is marked
+/// by a [`FINISHED_MARKER`] file inside it; a registered system JDK is a
+/// symlink whose target jpre does not own (and must not write markers into), so
+/// the symlink itself is the completeness signal.
+fn is_finished(path: &Path) -> bool {
+    match path.symlink_metadata() {
+        Ok(meta) if meta.file_type().is_symlink() => true,
+        Ok(_) => path.join(FINISHED_MARKER).exists(),
+        Err(_) => false,
+    }
+}
+
+/// The distribution a given major is (or will be) served from: whatever the
+/// `.jpre_dist` marker records, otherwise the configured default. Keeping this
+/// sticky per major means re-downloads and version checks stay on one vendor.
+fn selected_distribution(major: u8) -> Box<dyn Distribution> {
+    let marker = BASE_PATH.join(major.to_string()).join(DIST_MARKER);
+    std::fs::read_to_string(&marker)
+        .ok()
+        .and_then(|id| distribution::for_id(id.trim()))
+        .unwrap_or_else(distribution::configured_default)
+}
 
 pub fn get_jdk_version(major: u8) -> Option<String> {
     let path = BASE_PATH.join(major.to_string());
-    if !path.join(FINISHED_MARKER).exists() {
+    if !is_finished(&path) {
         debug!("No finished marker exists in JDK {}", major);
         return None;
     }
-    let release = path.join("release");
-    if !path.join("release").exists() {
-        debug!("No release file exists in JDK {}", major);
+    read_release_version(&path)
+}
+
+/// Read the `JAVA_VERSION` entry from the `release` file of an arbitrary JDK
+/// install directory, independent of whether jpre manages it.
+fn read_release_version(jdk_dir: &Path) -> Option<String> {
+    let release = jdk_dir.join("release");
+    if !release.exists() {
+        debug!("No release file exists in {}", jdk_dir.display());
         return None;
     }
     let config = std::fs::read_to_string(release)
@@ -69,6 +108,19 @@ pub fn get_jdk_version(major: u8) -> Option<String> {
     }
 }
 
+/// Extract the major version from a `JAVA_VERSION` string, handling both the
+/// modern `21.0.5` form and the legacy `1.8.0_422` form.
+fn major_of_version(version: &str) -> Option<u8> {
+    let trimmed = version.trim().trim_matches('"');
+    let mut parts = trimmed.split('.');
+    let first = parts.next()?;
+    if first == "1" {
+        parts.next()?.parse().ok()
+    } else {
+        first.parse().ok()
+    }
+}
+
 pub fn get_all_jdk_majors() -> Result<Vec<u8>> {
     let read_dir_result = BASE_PATH.read_dir();
     if let Err(read_dir_error) = read_dir_result {
@@ -105,6 +157,79 @@ pub fn get_all_jdk_majors() -> Result<Vec<u8>> {
         .collect();
 }
 
+/// The directory that actually holds a JDK's `release` file for a given home,
+/// accounting for the macOS `Contents/Home` bundle layout.
+fn resolve_jdk_home(home: &Path) -> PathBuf {
+    let mac_home = home.join("Contents/Home");
+    if mac_home.join("release").exists() {
+        mac_home
+    } else {
+        home.to_path_buf()
+    }
+}
+
+/// Locate JDKs already installed on the system: first `JAVA_HOME`, then every
+/// `java` reachable via `PATH`, resolved back to its real install directory.
+pub fn discover_system_jdks() -> Result<Vec<(u8, PathBuf)>> {
+    let mut candidates: Vec<PathBuf> = Vec::new();
+    if let Some(java_home) = std::env::var_os("JAVA_HOME") {
+        candidates.push(PathBuf::from(java_home));
+    }
+    if let Some(path) = std::env::var_os("PATH") {
+        for dir in std::env::split_paths(&path) {
+            let java = dir.join("java");
+            if !java.exists() {
+                continue;
+            }
+            // Follow symlinks (e.g. /usr/bin/java) to the real <home>/bin/java.
+            let real = std::fs::canonicalize(&java).unwrap_or(java);
+            if let Some(home) = real.parent().and_then(|bin| bin.parent()) {
+                candidates.push(home.to_path_buf());
+            }
+        }
+    }
+
+    let mut discovered: Vec<(u8, PathBuf)> = Vec::new();
+    let mut seen: std::collections::HashSet<PathBuf> = std::collections::HashSet::new();
+    for candidate in candidates {
+        let home = resolve_jdk_home(&candidate);
+        if !seen.insert(home.clone()) {
+            continue;
+        }
+        if let Some(major) = read_release_version(&home).as_deref().and_then(major_of_version) {
+            discovered.push((major, home));
+        }
+    }
+    Ok(discovered)
+}
+
+/// Register discovered system JDKs into `BASE_PATH` as symlinks so that
+/// [`symlink_jdk_path`] and [`get_current_jdk`] treat them like managed ones.
+/// Majors already backed by a managed install are left untouched.
+pub fn register_system_jdks() -> Result<Vec<u8>> {
+    let mut registered = Vec::new();
+    for (major, home) in discover_system_jdks()? {
+        let path = BASE_PATH.join(major.to_string());
+        if is_finished(&path) {
+            // Already satisfied by a managed (or previously registered) JDK.
+            continue;
+        }
+        if path.symlink_metadata().is_ok() {
+            std::fs::remove_file(&path)
+                .or_else(|_| std::fs::remove_dir_all(&path))
+                .with_context(|| format!("Failed to clear stale entry ({})", path.display()))?;
+        }
+        create_dir_all(&*BASE_PATH).context("Failed to create base directory")?;
+        // The symlink itself marks completeness; we never write a marker into
+        // the external (often root-owned) install we don't control.
+        std::os::unix::fs::symlink(&home, &path)
+            .with_context(|| format!("Failed to link system JDK {}", home.display()))?;
+        registered.push(major);
+    }
+    registered.sort_unstable();
+    Ok(registered)
+}
+
 pub fn map_available_jdk_versions(majors: &Vec<u8>) -> Vec<(u8, String)> {
     let mut vec: Vec<(u8, String)> = majors
         .iter()
@@ -126,7 +251,7 @@ pub fn symlink_jdk_path(major: u8) -> Result<()> {
 
 pub fn get_jdk_path(major: u8) -> Result<PathBuf> {
     let path = BASE_PATH.join(major.to_string());
-    if path.join(FINISHED_MARKER).exists() {
+    if is_finished(&path) {
         return Ok(path);
     }
 
@@ -134,58 +259,218 @@ pub fn get_jdk_path(major: u8) -> Result<PathBuf> {
     return Ok(path);
 }
 
+/// Whether an update is available for a locally installed major.
+#[derive(Debug, Clone)]
+pub enum UpdateStatus {
+    /// No local install, so nothing to compare against.
+    NotInstalled,
+    /// The local version matches (or is newer than) the latest advertised.
+    UpToDate { current: String },
+    /// A newer version is available upstream.
+    Available { current: String, latest: String },
+    /// The distribution does not advertise a version cheaply.
+    Unknown { current: String },
+}
+
+/// Compare two dotted `JAVA_VERSION` strings, mirroring the "program out of
+/// date" comparison used elsewhere. Only the components both sides carry are
+/// compared, so a richer advertised string (e.g. `21.0.5+11`) does not read as
+/// newer than an equivalent local `release` value (`21.0.5`).
+fn remote_is_newer(local: &str, remote: &str) -> bool {
+    fn parts(version: &str) -> Vec<u64> {
+        version
+            .trim()
+            .trim_matches('"')
+            .split(|c: char| c == '.' || c == '_' || c == '+' || c == '-')
+            .map(|p| p.parse::<u64>().unwrap_or(0))
+            .collect()
+    }
+    let (local, remote) = (parts(local), parts(remote));
+    for i in 0..local.len().min(remote.len()) {
+        if remote[i] != local[i] {
+            return remote[i] > local[i];
+        }
+    }
+    false
+}
+
+/// Report whether a newer release of `major` has shipped upstream without
+/// downloading anything.
+pub fn check_update(major: u8) -> Result<UpdateStatus> {
+    let current = match get_jdk_version(major) {
+        Some(version) => version,
+        None => return Ok(UpdateStatus::NotInstalled),
+    };
+    match selected_distribution(major).latest_version(major)? {
+        Some(latest) if remote_is_newer(&current, &latest) => {
+            Ok(UpdateStatus::Available { current, latest })
+        }
+        Some(_) => Ok(UpdateStatus::UpToDate { current }),
+        None => Ok(UpdateStatus::Unknown { current }),
+    }
+}
+
+/// Check-only mode: report update availability for every installed major.
+pub fn check_all_updates() -> Result<Vec<(u8, UpdateStatus)>> {
+    get_all_jdk_majors()?
+        .into_iter()
+        .map(|major| check_update(major).map(|status| (major, status)))
+        .collect()
+}
+
+/// Re-download `major` only when the distribution advertises a newer version,
+/// or install it when it is not installed at all. Nothing is downloaded when
+/// the distribution advertises no version. Returns whether an update was
+/// actually performed.
+pub fn update_jdk_if_stale(major: u8) -> Result<bool> {
+    match check_update(major)? {
+        UpdateStatus::Available { .. } | UpdateStatus::NotInstalled => {
+            update_jdk(major)?;
+            Ok(true)
+        }
+        _ => Ok(false),
+    }
+}
+
 pub fn update_jdk(major: u8) -> Result<()> {
     let path = BASE_PATH.join(major.to_string());
-    let response = adoptjdk::get_latest_jdk_binary(major)?;
+    let dist = selected_distribution(major);
+    let response = dist.latest_binary(major)?;
     if !response.is_success() {
         return Err(handle_response_fail(response, "Failed to get JDK binary"));
     }
 
-    let url = response
+    let expected_sha256 = dist
+        .latest_checksum(major)
+        .context("Failed to get expected JDK checksum")?;
+    // Prefer the filename Adoptium advertises via Content-Disposition, but fall
+    // back to the vendor's statically-known extension for CDNs (Corretto,
+    // GraalVM, Zulu) that stream the binary without that header.
+    let url = match response
         .headers()
         .get(attohttpc::header::CONTENT_DISPOSITION)
-        .ok_or_else(|| anyhow!("no content disposition"))
-        .and_then(|value| parse_filename(value.to_str()?))?;
+        .map(|value| parse_filename(value.to_str()?))
+        .transpose()?
+    {
+        Some(filename) => filename,
+        None => format!("{}{}", major, dist.archive_extension()),
+    };
     eprintln!("Extracting {}", url);
-    if path.exists() {
-        std::fs::remove_dir_all(&path)
-            .with_context(|| format!("Unable to clean JDK folder ({})", path.display()))?;
-    }
-    create_dir_all(&path).with_context(|| {
-        format!(
-            "Unable to create directories to JDK folder ({})",
-            path.display()
-        )
+    // Build the new install out of the way so the existing good copy survives an
+    // interruption; nothing touches `path` until the new tree is complete.
+    let new_path = BASE_PATH.join(format!("{}.new", major));
+    if new_path.exists() {
+        std::fs::remove_dir_all(&new_path)
+            .with_context(|| format!("Unable to clean staging folder ({})", new_path.display()))?;
+    }
+    create_dir_all(&*BASE_PATH).with_context(|| {
+        format!("Unable to create base directory ({})", BASE_PATH.display())
     })?;
     let temporary_dir = TempDir::new_in(&*BASE_PATH, "jdk-download")
         .context("Failed to create temporary directory")?;
-    finish_extract(&path, response, url, &temporary_dir).and_then(|_| {
+    finish_extract(&new_path, response, url, expected_sha256, &temporary_dir).and_then(|_| {
         if temporary_dir.path().exists() {
             temporary_dir.close().context("Failed to cleanup temp dir")
         } else {
             Ok(())
         }
     })?;
+    // Record the vendor next to the finished marker inside the staged tree.
+    std::fs::write(new_path.join(DIST_MARKER), dist.id())
+        .with_context(|| format!("Unable to record distribution for ({})", new_path.display()))?;
+
+    // Swap: move any existing copy aside, move the new tree into place, then
+    // delete the old copy. `path` is only ever absent or a complete tree. A
+    // crash between the two renames leaves `path` absent (so `get_jdk_path`
+    // downloads again) and the trees at `<major>.old`/`<major>.new`, which
+    // `reap_incomplete_installs` removes.
+    let old_path = BASE_PATH.join(format!("{}.old", major));
+    if old_path.symlink_metadata().is_ok() {
+        remove_any(&old_path)
+            .with_context(|| format!("Unable to clean old folder ({})", old_path.display()))?;
+    }
+    if path.symlink_metadata().is_ok() {
+        std::fs::rename(&path, &old_path)
+            .with_context(|| format!("Unable to move aside old JDK ({})", path.display()))?;
+    }
+    std::fs::rename(&new_path, &path)
+        .with_context(|| format!("Unable to move new JDK into place ({})", path.display()))?;
+    if old_path.symlink_metadata().is_ok() {
+        // Best-effort; any leftover is reaped by `reap_incomplete_installs`.
+        let _ = remove_any(&old_path);
+    }
     return Ok(());
 }
 
+/// Remove leftover staging (`<major>.new`), retired (`<major>.old`), and
+/// `jdk-download` temp directories from an interrupted install. Safe to call on
+/// startup: a coherent `BASE_PATH/<major>` is never affected.
+pub fn reap_incomplete_installs() -> Result<()> {
+    let read_dir_result = BASE_PATH.read_dir();
+    if let Err(error) = &read_dir_result {
+        return if error.kind() == std::io::ErrorKind::NotFound {
+            Ok(())
+        } else {
+            Err(read_dir_result.unwrap_err())?
+        };
+    }
+    for entry in read_dir_result.unwrap() {
+        let entry = entry.context("Failed to read base directory entry")?;
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        if name.ends_with(".new") || name.ends_with(".old") || name.starts_with("jdk-download") {
+            let path = entry.path();
+            debug!("Reaping leftover install dir {}", path.display());
+            let _ = remove_any(&path);
+        }
+    }
+    Ok(())
+}
+
 fn finish_extract(
     path: &PathBuf,
     response: attohttpc::Response,
     url: String,
+    expected_sha256: Option<String>,
     temporary_dir: &TempDir,
 ) -> Result<()> {
-    if url.ends_with(".tar.gz") {
-        let expected_size = response.headers().get("Content-length").and_then(|len| {
-            len.to_str()
-                .ok()
-                .and_then(|len_str| len_str.parse::<u64>().ok())
-        });
-        unarchive_tar_gz(temporary_dir.path(), expected_size, response)
+    let expected_size = response.headers().get("Content-length").and_then(|len| {
+        len.to_str()
+            .ok()
+            .and_then(|len_str| len_str.parse::<u64>().ok())
+    });
+    // Hash the bytes as they stream past, beneath the download progress bar, so we
+    // can verify the finished download without ever buffering the whole tarball.
+    let hasher = Arc::new(Mutex::new(Sha256::new()));
+    let reader = HashingReader {
+        inner: response,
+        hasher: hasher.clone(),
+    };
+    let handle = if url.ends_with(".tar.gz") {
+        unarchive_tar_gz(temporary_dir.path(), expected_size, reader)
+    } else if url.ends_with(".tar.xz") {
+        unarchive_tar_xz(temporary_dir.path(), expected_size, reader)
+    } else if url.ends_with(".zip") {
+        unarchive_zip(temporary_dir.path(), expected_size, reader)
     } else {
         return Err(anyhow!("Don't know how to handle {}", url));
-    }
+    };
     eprintln!();
+    // Wait for the extraction thread to drain the reader to EOF and finish all
+    // hasher updates before reading the digest.
+    handle
+        .join()
+        .map_err(|_| anyhow!("Extraction thread panicked"))?;
+    if let Some(expected) = expected_sha256 {
+        let actual = hex::encode(hasher.lock().unwrap().clone().finalize());
+        if !actual.eq_ignore_ascii_case(expected.trim()) {
+            return Err(anyhow!(
+                "Checksum mismatch: expected {}, got {}",
+                expected.trim(),
+                actual
+            ));
+        }
+    }
     let dir_entries = temporary_dir
         .path()
         .read_dir()
@@ -220,14 +505,33 @@ fn finish_extract(
     Ok(())
 }
 
-fn unarchive_tar_gz(path: &Path, expected_size: Option<u64>, reader: impl Read + Send + 'static) {
+/// A [`Read`] adapter that feeds every byte it passes through into a shared
+/// [`Sha256`], so the digest is computed incrementally during extraction.
+struct HashingReader<R> {
+    inner: R,
+    hasher: Arc<Mutex<Sha256>>,
+}
+
+impl<R: Read> Read for HashingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.hasher.lock().unwrap().update(&buf[..read]);
+        Ok(read)
+    }
+}
+
+fn unarchive_tar_gz(
+    path: &Path,
+    expected_size: Option<u64>,
+    reader: impl Read + Send + 'static,
+) -> std::thread::JoinHandle<()> {
     let all_bars = MultiProgress::with_draw_target(ProgressDrawTarget::stderr());
     let download_bar = all_bars.add(new_progress_bar(expected_size));
     download_bar.set_message("Download progress");
     let writing_bar = all_bars.add(new_progress_bar(None));
 
     let static_path = path.to_path_buf();
-    let _ = std::thread::spawn(move || {
+    let handle = std::thread::spawn(move || {
         let gz_decode = libflate::gzip::Decoder::new(BufReader::new(download_bar.wrap_read(reader))).unwrap();
         let mut archive = tar::Archive::new(BufReader::new(writing_bar.wrap_read(gz_decode)));
         archive.set_preserve_permissions(true);
@@ -237,9 +541,101 @@ fn unarchive_tar_gz(path: &Path, expected_size: Option<u64>, reader: impl Read +
             writing_bar.set_message(&*format!("Extracting {}", file.path().unwrap().display()));
             file.unpack_in(&static_path).unwrap();
         }
+        // `tar` stops at the end-of-archive marker; drain the rest so the
+        // compression trailer and padding flow through the hashing reader and
+        // the digest covers the whole file.
+        io::copy(&mut archive.into_inner(), &mut io::sink()).unwrap();
+        download_bar.finish();
+        writing_bar.abandon_with_message("Done extracting!");
+    });
+
+    all_bars.join().unwrap();
+    handle
+}
+
+fn unarchive_tar_xz(
+    path: &Path,
+    expected_size: Option<u64>,
+    reader: impl Read + Send + 'static,
+) -> std::thread::JoinHandle<()> {
+    let all_bars = MultiProgress::with_draw_target(ProgressDrawTarget::stderr());
+    let download_bar = all_bars.add(new_progress_bar(expected_size));
+    download_bar.set_message("Download progress");
+    let writing_bar = all_bars.add(new_progress_bar(None));
+
+    let static_path = path.to_path_buf();
+    let handle = std::thread::spawn(move || {
+        let xz_decode = xz2::bufread::XzDecoder::new(BufReader::new(download_bar.wrap_read(reader)));
+        let mut archive = tar::Archive::new(BufReader::new(writing_bar.wrap_read(xz_decode)));
+        archive.set_preserve_permissions(true);
+        archive.set_overwrite(true);
+        for entry in archive.entries().unwrap() {
+            let mut file = entry.unwrap();
+            writing_bar.set_message(&*format!("Extracting {}", file.path().unwrap().display()));
+            file.unpack_in(&static_path).unwrap();
+        }
+        // Drain past the end-of-archive marker so the digest sees the whole file.
+        io::copy(&mut archive.into_inner(), &mut io::sink()).unwrap();
         download_bar.finish();
         writing_bar.abandon_with_message("Done extracting!");
     });
 
     all_bars.join().unwrap();
+    handle
+}
+
+fn unarchive_zip(
+    path: &Path,
+    expected_size: Option<u64>,
+    reader: impl Read + Send + 'static,
+) -> std::thread::JoinHandle<()> {
+    let all_bars = MultiProgress::with_draw_target(ProgressDrawTarget::stderr());
+    let download_bar = all_bars.add(new_progress_bar(expected_size));
+    download_bar.set_message("Download progress");
+    let writing_bar = all_bars.add(new_progress_bar(None));
+
+    let static_path = path.to_path_buf();
+    let handle = std::thread::spawn(move || {
+        // Zip's central directory lives at the end of the file, so a pure stream
+        // can't be read reliably: entries using a trailing data descriptor
+        // (general-purpose bit 3), which streamed/zip64 JDK archives commonly
+        // set, have unknown sizes mid-stream. Buffer the download to a seekable
+        // temp file — this also drains the reader to EOF so the digest is
+        // complete — then read it back with a random-access `ZipArchive`.
+        let archive_path = static_path.join(".jpre-download.zip");
+        let mut buffered = File::create(&archive_path).unwrap();
+        io::copy(&mut download_bar.wrap_read(reader), &mut buffered).unwrap();
+        download_bar.finish();
+        buffered.seek(SeekFrom::Start(0)).unwrap();
+
+        let mut archive = zip::ZipArchive::new(buffered).unwrap();
+        for i in 0..archive.len() {
+            let mut file = archive.by_index(i).unwrap();
+            let relative = match file.enclosed_name() {
+                Some(name) => name.to_path_buf(),
+                None => continue,
+            };
+            let out_path = static_path.join(&relative);
+            writing_bar.set_message(&*format!("Extracting {}", relative.display()));
+            if file.is_dir() {
+                create_dir_all(&out_path).unwrap();
+            } else {
+                if let Some(parent) = out_path.parent() {
+                    create_dir_all(parent).unwrap();
+                }
+                let mut out_file = File::create(&out_path).unwrap();
+                io::copy(&mut file, &mut out_file).unwrap();
+            }
+            // Preserve the unix permission bits carried in the external attributes.
+            if let Some(mode) = file.unix_mode() {
+                use std::os::unix::fs::PermissionsExt;
+                std::fs::set_permissions(&out_path, std::fs::Permissions::from_mode(mode)).unwrap();
+            }
+        }
+        std::fs::remove_file(&archive_path).ok();
+        writing_bar.abandon_with_message("Done extracting!");
+    });
+
+    all_bars.join().unwrap();
+    handle
 }